@@ -0,0 +1,51 @@
+;; A representative medium sized program, used as benchmark input.
+#| It mixes definitions, strings, chars, numbers,
+   quoting and comments so every part of the lexer gets exercised. |#
+
+(define (square x) (* x x))
+
+(define (fact n)
+  (if (= n 0)
+      1
+      (* n (fact (- n 1)))))
+
+(define (fib n)
+  (let loop ((a 0) (b 1) (i 0))
+    (if (= i n)
+        a
+        (loop b (+ a b) (+ i 1)))))
+
+(define (map-tree f tree)
+  (cond ((null? tree) '())
+        ((pair? tree) (cons (map-tree f (car tree))
+                            (map-tree f (cdr tree))))
+        (else (f tree))))
+
+(define (string-repeat s n)
+  (if (<= n 0)
+      ""
+      (string-append s (string-repeat s (- n 1)))))
+
+(define greeting "Hello,\tworld!\n")
+(define separators (list #\space #\tab #\newline #\, #\x3bb))
+(define numbers '(1 -2 +3 42 1000000 -17))
+(define table #(one two three))
+(define bytes #u8(0 1 2 255))
+
+(define-syntax swap!
+  (syntax-rules ()
+    ((_ a b) (let ((tmp a)) (set! a b) (set! b tmp)))))
+
+(define (classify x) ; a line comment after code
+  (cond ((number? x) 'number)
+        ((string? x) 'string)
+        ((char? x) 'char)
+        (else 'unknown)))
+
+(display (map-tree square '(1 (2 3) (4 (5)))))
+(display (fact 10))
+(display (fib 30))
+(display (string-repeat greeting 3))
+(display `(classified ,(classify 1) #;(ignored datum) ,(classify "s")))
+(display |a symbol with spaces|)
+#t #f #true #false
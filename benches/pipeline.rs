@@ -0,0 +1,36 @@
+//! Whole-pipeline throughput benchmarks.
+//!
+//! Only the lexer exists so far, so that is the only stage measured, both in bytes/s and in
+//! tokens/s. The parse and eval benchmarks wait on a parser and an evaluator and belong here
+//! as those stages land.
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use scheme_rs::Lexer;
+
+/// a representative medium sized program
+const MEDIUM: &str = include_str!("medium.scm");
+
+fn lex_all(source: &str) -> usize {
+    Lexer::new(source).count()
+}
+
+fn lexer(c: &mut Criterion) {
+    let large = MEDIUM.repeat(100);
+    let mut group = c.benchmark_group("lexer");
+    for (name, source) in [("medium", MEDIUM), ("large", large.as_str())] {
+        let throughputs = [
+            ("bytes", Throughput::Bytes(source.len() as u64)),
+            ("tokens", Throughput::Elements(lex_all(source) as u64)),
+        ];
+        for (unit, throughput) in throughputs {
+            group.throughput(throughput);
+            group.bench_function(BenchmarkId::new(unit, name), |b| {
+                b.iter(|| lex_all(black_box(source)))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lexer);
+criterion_main!(benches);
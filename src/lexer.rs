@@ -1,58 +1,95 @@
-//std lib imports
-use std::collections::HashSet;
-use std::{iter::Peekable, str::Chars};
-// external imports
-use lazy_static::lazy_static;
+//core/alloc imports, the lexer doesn't depend on std
+use alloc::format;
+use alloc::string::String;
+use core::iter::FusedIterator;
+use core::str::Chars;
 // internal imports
-use crate::tokens::{LiteralKind, Token};
+use crate::tokens::{Exactness, LiteralKind, NumberLiteral, Span, Spanned, Token, CHAR_NAMES};
 
-lazy_static! {
-    /// extended identification chars
-    static ref EXTENDED_IDENT_CHARS: HashSet<char> = HashSet::from(['!', '$', '%', '&', '*', '+', '-', '.', '/', ':', '<', '=', '>', '?', '@', '^', '_', '~']);
-}
+/// extended identification chars
+const EXTENDED_IDENT_CHARS: [char; 18] = [
+    '!', '$', '%', '&', '*', '+', '-', '.', '/', ':', '<', '=', '>', '?', '@', '^', '_', '~',
+];
 
-/// Directives that designate whether an identifier should use be case agnostic
-const DIRECTIVES: [&'static str; 2] = ["#!fold-case", "#!no-fold-case"];
+/// Directives that designate whether an identifier should use be case agnostic.
+/// Written without the leading "#!", the same way `Token::Directive` holds them.
+const DIRECTIVES: [&str; 2] = ["fold-case", "no-fold-case"];
 
 pub const EOF_CHAR: char = '\0';
 
 /// The Lexer. Taking heavy inspiration of the rustc_lexer Cursor struct
 pub struct Lexer<'a> {
-    len_remaining: usize,
+    /// length in bytes of the whole input
+    input_len: usize,
     chars: Chars<'a>,
+    /// line of the next char, starting at 1
+    line: usize,
+    /// column of the next char counted in chars, starting at 1
+    col: usize,
+    /// span of the last token returned by `next_token`
+    span: Span,
+    /// set by `#!fold-case` and cleared by `#!no-fold-case`. Lowercases identifiers while set
+    fold_case: bool,
 }
 
 // Here we implement some tooling
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Self {
         Self {
-            len_remaining: input.len(),
+            input_len: input.len(),
             chars: input.chars(),
+            line: 1,
+            col: 1,
+            span: Span {
+                start: 0,
+                end: 0,
+                line: 1,
+                col: 1,
+            },
+            fold_case: false,
         }
     }
 
+    /// Number of bytes of the input consumed so far.
+    pub fn byte_pos(&self) -> usize {
+        self.input_len - self.chars.as_str().len()
+    }
+
+    /// The part of the input that hasn't been consumed yet.
+    pub fn remaining(&self) -> &'a str {
+        self.chars.as_str()
+    }
+
+    // peeks the char n positions ahead without consuming anything, 0 being the next char
+    fn nth_char(&self, n: usize) -> Option<char> {
+        self.chars.clone().nth(n)
+    }
+
     // peeks the next char
     fn first(&self) -> Option<char> {
-        self.chars.clone().next()
+        self.nth_char(0)
     }
 
     // peeks the second char
     fn second(&self) -> Option<char> {
-        let mut iter = self.chars.clone();
-        iter.next();
-        iter.next()
+        self.nth_char(1)
     }
 
     // peeks the third char
     fn third(&self) -> Option<char> {
-        let mut iter = self.chars.clone();
-        iter.next();
-        iter.next();
-        iter.next()
+        self.nth_char(2)
     }
 
     fn bump(&mut self) -> Option<char> {
-        self.chars.next()
+        let c = self.chars.next()?;
+        // a bare '\r' is an old Mac line ending, in a "\r\n" pair only the '\n' ends the line
+        if c == '\n' || (c == '\r' && self.first() != Some('\n')) {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        Some(c)
     }
 
     fn take_while(&mut self, mut predicate: impl FnMut(char) -> bool) -> String {
@@ -73,25 +110,50 @@ impl<'a> Lexer<'a> {
 
 // here we define the syntax specific tooling
 impl<'a> Lexer<'a> {
+    /// Consumes and returns the next token of the input.
+    ///
+    /// Once the input is exhausted every further call returns `Token::EOF`, no matter how many
+    /// times it is called.
     pub fn next_token(&mut self) -> Token {
-        if cfg!(debug_assertions) {
-            println!(
-                "begining 'next_token' call with chars: {:?}",
-                self.chars.clone().collect::<Vec<char>>()
-            )
-        }
-        use Token::*;
         // first we consume as much whitespace as we can
         self.eat_while(|c| c.is_whitespace());
 
+        let (start, line, col) = (self.byte_pos(), self.line, self.col);
+        let token = self.token();
+        self.span = Span {
+            start,
+            end: self.byte_pos(),
+            line,
+            col,
+        };
+        token
+    }
+
+    /// Like `next_token`, but also returns where in the input the token is.
+    pub fn next_spanned_token(&mut self) -> Spanned<Token> {
+        let token = self.next_token();
+        Spanned {
+            value: token,
+            span: self.span,
+        }
+    }
+
+    /// The span of the last token returned by `next_token`.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Lexes a token starting at the next char, which isn't whitespace.
+    fn token(&mut self) -> Token {
+        use Token::*;
         // try consuming a char
         let first_char = match self.bump() {
             Some(c) => c,
             None => return EOF,
         };
         // Based on some char patterns we will opportunistically try to consume more of the input.
-        // Every method used to consume further might however return `Token::Error` instead if they were
-        // unable to parse the consumed chars as expected.
+        // Every method used to consume further might however return a `Token::Error` explaining why
+        // they were unable to parse the consumed chars as expected.
         let token_kind = match (first_char, self.first(), self.second(), self.third()) {
             // Single char tokens
             ('(', _, _, _) => OpenParen,
@@ -102,10 +164,12 @@ impl<'a> Lexer<'a> {
             ('}', _, _, _) => CloseCurlyParen, // reserved
             ('\'', _, _, _) => Apost,          // denotes literal data
             ('`', _, _, _) => Grave,           // denotes partially constant data
-            ('`', _, _, _) => Grave,           // denotes partially constant data
+            (',', Some('@'), _, _) => self.comma_at(),
+            (',', _, _, _) => Comma,
             // comments
             (';', _, _, _) => self.line_comment(),
             ('#', Some('|'), _, _) => self.block_comment(),
+            ('#', Some(';'), _, _) => self.datum_comment(),
             // directive
             ('#', Some('!'), _, _) => self.directive(),
             ('#', Some(c), _, _) if c == 't' || c == 'f' => self.boolean(),
@@ -113,13 +177,22 @@ impl<'a> Lexer<'a> {
             ('#', Some(c), _, _) if c.is_numeric() => self.datum(),
             // litterals
             ('"', _, _, _) => self.string_literal(),
+            ('#', Some('\\'), _, _) => self.char_literal(),
             // some list types
             ('#', Some('u'), Some('8'), Some('(')) => self.bytevector(),
             ('#', Some('('), _, _) => self.vector(),
+            // numbers, a lone sign is an identifier though
+            ('#', Some(c), _, _) if is_number_prefix(c) => self.prefixed_number(),
+            (c, _, _, _) if c.is_ascii_digit() => self.number(c),
+            ('+' | '-', Some(c), _, _) if c.is_ascii_digit() => self.number(first_char),
+            ('+' | '-', Some('.'), Some(c), _) if c.is_ascii_digit() => self.number(first_char),
+            ('.', Some(c), _, _) if c.is_ascii_digit() => self.number(first_char),
+            // a lone '.' for dotted pairs, unlike e.g. `...` or `.foo`
+            ('.', next, _, _) if !next.is_some_and(is_identifier_char) => Dot,
             // identifiers
             ('|', _, _, _) => self.pipe_identifier(),
             (i, _, _, _) if is_valid_first_letter_ident(i) => self.identifier(i), // a valid ident may not begin with a number or consist of a single '.'
-            _ => Error,
+            (c, _, _, _) => Error(format!("unexpected character '{c}'")),
         };
 
         // if we've been unsuccessfull in  matching some known syntax,
@@ -129,8 +202,8 @@ impl<'a> Lexer<'a> {
     fn datum(&mut self) -> Token {
         // we take all the numbers
         let content = self.take_while(|c| c.is_numeric());
-        if content.len() == 0 {
-            Token::Error
+        if content.is_empty() {
+            Token::Error("expected a datum label number".into())
         } else if self.first() == Some('#') {
             self.bump();
             Token::DatumRef(content)
@@ -138,8 +211,107 @@ impl<'a> Lexer<'a> {
             self.bump();
             Token::DatumOpen(content)
         } else {
-            Token::Error
+            Token::Error(format!("datum label #{content} must end with '=' or '#'"))
+        }
+    }
+
+    fn number(&mut self, first_char: char) -> Token {
+        self.real(String::from(first_char), 10, None)
+    }
+
+    fn prefixed_number(&mut self) -> Token {
+        // up to one radix and one exactness prefix, in either order
+        let mut radix = None;
+        let mut exactness = None;
+        loop {
+            match self.bump().map(|c| c.to_ascii_lowercase()) {
+                Some('e') if exactness.is_none() => exactness = Some(Exactness::Exact),
+                Some('i') if exactness.is_none() => exactness = Some(Exactness::Inexact),
+                Some('b') if radix.is_none() => radix = Some(2),
+                Some('o') if radix.is_none() => radix = Some(8),
+                Some('d') if radix.is_none() => radix = Some(10),
+                Some('x') if radix.is_none() => radix = Some(16),
+                // duplicate or conflicting prefixes, e.g. `#e#i1`
+                _ => return self.malformed_number("duplicate or conflicting number prefix"),
+            }
+            if self.first() == Some('#') {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        let mut content = String::new();
+        if let Some(sign @ ('+' | '-')) = self.first() {
+            self.bump();
+            content.push(sign);
+        }
+        self.real(content, radix.unwrap_or(10), exactness)
+    }
+
+    /// Lexes the rest of a real number in the given radix.
+    /// `content` is what has already been consumed of it after the prefixes: sign and/or first char.
+    fn real(&mut self, mut content: String, radix: u32, exactness: Option<Exactness>) -> Token {
+        let started_with_dot = content.ends_with('.');
+        let mut has_digits = content.ends_with(|c: char| c.is_ascii_digit());
+        let digits = self.take_while(|c| c.is_digit(radix));
+        has_digits |= !digits.is_empty();
+        content.push_str(&digits);
+        if has_digits && !started_with_dot && self.first() == Some('/') {
+            // a rational, `numerator/denominator`. A zero denominator is an evaluation error
+            content.push(self.bump().unwrap());
+            let denominator = self.take_while(|c| c.is_digit(radix));
+            if denominator.is_empty() {
+                return self.malformed_number("rational number is missing its denominator");
+            }
+            content.push_str(&denominator);
+        } else if radix == 10 {
+            // fractional numbers and exponents are only allowed in decimal, e.g. `#x1.8` is an error
+            // fractional part, unless the number started with the '.'
+            if !started_with_dot && self.first() == Some('.') {
+                content.push(self.bump().unwrap());
+                let digits = self.take_while(|c| c.is_ascii_digit());
+                has_digits |= !digits.is_empty();
+                content.push_str(&digits);
+            }
+            // exponent
+            if let Some(marker @ ('e' | 'E')) = self.first() {
+                self.bump();
+                content.push(marker);
+                if let Some(sign @ ('+' | '-')) = self.first() {
+                    self.bump();
+                    content.push(sign);
+                }
+                let exponent = self.take_while(|c| c.is_ascii_digit());
+                if exponent.is_empty() {
+                    return self.malformed_number("exponent has no digits");
+                }
+                content.push_str(&exponent);
+            }
+        }
+        if !has_digits {
+            return self.malformed_number("number has no digits");
+        }
+        // anything else glued to the number, e.g. `1.2.3`, `1e2e3`, `1/2/3` or `#b102`
+        match self.first() {
+            Some('.') if radix != 10 => {
+                return self.malformed_number("fractional numbers require decimal radix")
+            }
+            Some(c) if c.is_alphanumeric() || c == '.' || c == '/' => {
+                return self.malformed_number("malformed number")
+            }
+            _ => {}
         }
+        Token::Literal(LiteralKind::Number(NumberLiteral {
+            text: content,
+            radix,
+            exactness,
+        }))
+    }
+
+    /// Consumes what is left of a malformed number and returns an error for it.
+    fn malformed_number(&mut self, message: &str) -> Token {
+        self.eat_while(is_identifier_char);
+        Token::Error(message.into())
     }
 
     fn identifier(&mut self, first_letter: char) -> Token {
@@ -148,14 +320,10 @@ impl<'a> Lexer<'a> {
         while self.first().is_some() && is_identifier_char(self.first().unwrap()) {
             content.push(self.bump().unwrap());
         }
-        // only include this on debug builds
-        if cfg!(debug_assertions) {
-            println!(
-                "content: {content}, chars left: {:?}",
-                self.chars.clone().collect::<Vec<char>>()
-            );
+        // pipe identifiers, strings and chars are never folded, only plain identifiers
+        if self.fold_case {
+            content = content.to_lowercase();
         }
-
         Token::Identifier(content)
     }
 
@@ -172,34 +340,157 @@ impl<'a> Lexer<'a> {
     }
 
     fn boolean(&mut self) -> Token {
-        let content = self.take_while(|c| c.is_whitespace());
+        // the 't' or 'f', plus the rest of `true`/`false` if it's spelled out
+        let content = self.take_while(is_identifier_char);
         let c = &content;
         if c == "t" || c == "true" || c == "f" || c == "false" {
             Token::Literal(LiteralKind::Boolean(content))
         } else {
-            Token::Error
+            Token::Error(format!("invalid boolean #{content}"))
         }
     }
 
     fn pipe_identifier(&mut self) -> Token {
-        let mut content = String::from('|');
-        content.push_str(&(self.take_while(|c| c != '|')));
-        content.push('|');
-        let res = Token::Identifier(content);
-        self.bump();
-        res
+        match self.escaped_until('|', "pipe identifier") {
+            Ok(content) => Token::PipeIdentifier(content),
+            Err(message) => Token::Error(message),
+        }
     }
 
     fn string_literal(&mut self) -> Token {
-        let content = self.take_while(|c| c != '"');
-        let res = Token::Literal(LiteralKind::Str(content));
-        // trow away the second '"'
-        self.bump();
-        res
+        match self.escaped_until('"', "string") {
+            Ok(content) => Token::Literal(LiteralKind::Str(content)),
+            Err(message) => Token::Error(message),
+        }
+    }
+
+    /// Consumes chars up to and including the closing `delimiter`, processing escapes on the way.
+    /// `what` names the syntax being lexed for the error messages.
+    fn escaped_until(&mut self, delimiter: char, what: &str) -> Result<String, String> {
+        let mut content = String::new();
+        // an invalid escape doesn't stop us, we still consume up to the delimiter.
+        // Holds the line and column of the first invalid escape's '\\'
+        let mut invalid_at = None;
+        loop {
+            let (line, col) = (self.line, self.col);
+            match self.bump() {
+                Some(c) if c == delimiter => break,
+                Some('\\') => {
+                    // line continuations are only allowed in strings
+                    if delimiter == '"' && self.line_continuation() {
+                        continue;
+                    }
+                    match self.escape() {
+                        Some(c) => content.push(c),
+                        None => {
+                            invalid_at.get_or_insert((line, col));
+                        }
+                    }
+                }
+                Some(c) => content.push(c),
+                None => return Err(format!("unterminated {what}")),
+            }
+        }
+        match invalid_at {
+            None => Ok(content),
+            Some((line, col)) => Err(format!("invalid {what} escape at {line}:{col}")),
+        }
+    }
+
+    fn char_literal(&mut self) -> Token {
+        self.bump(); // throw away the '\'
+
+        // the char after '#\' is always part of the literal, whatever its syntactic role.
+        // That also makes `#\ ` the space char.
+        let first = match self.bump() {
+            Some(c) => c,
+            None => return Token::Error("expected a character after #\\".into()),
+        };
+        if !first.is_alphabetic() {
+            // like numbers, a char literal has to be followed by a delimiter, e.g. `#\1a` is an error
+            if self.first().is_some_and(is_identifier_char) {
+                let rest = self.take_while(is_identifier_char);
+                return Token::Error(format!("malformed character #\\{first}{rest}"));
+            }
+            return Token::Literal(LiteralKind::Char(first));
+        }
+        // a letter followed by more identifier chars is a named char, e.g. `#\space`
+        let mut name = String::from(first);
+        name.push_str(&self.take_while(is_identifier_char));
+        if name.chars().count() == 1 {
+            return Token::Literal(LiteralKind::Char(first));
+        }
+        // `#\x<hex>` denotes the char by its unicode scalar value
+        let hex = name
+            .strip_prefix('x')
+            .filter(|h| h.chars().all(|c| c.is_ascii_hexdigit()));
+        match hex {
+            Some(hex) => match u32::from_str_radix(hex, 16).ok().and_then(char::from_u32) {
+                Some(c) => Token::Literal(LiteralKind::Char(c)),
+                None => Token::Error(format!("#\\{name} is not a valid unicode scalar value")),
+            },
+            None => match char_name(&name) {
+                Some(c) => Token::Literal(LiteralKind::Char(c)),
+                None => Token::Error(format!("unknown character name #\\{name}")),
+            },
+        }
+    }
+
+    /// Consumes the chars following a '\' in a string or pipe identifier and returns the char
+    /// the escape denotes, or `None` if it isn't a valid escape.
+    fn escape(&mut self) -> Option<char> {
+        match self.bump()? {
+            'a' => Some('\u{7}'),
+            'b' => Some('\u{8}'),
+            't' => Some('\t'),
+            'n' => Some('\n'),
+            'r' => Some('\r'),
+            '"' => Some('"'),
+            '\\' => Some('\\'),
+            '|' => Some('|'),
+            'x' => {
+                let digits = self.take_while(|c| c.is_ascii_hexdigit());
+                if self.first() != Some(';') {
+                    return None;
+                }
+                self.bump();
+                u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+            }
+            _ => None,
+        }
+    }
+
+    /// Consumes a line continuation following a '\' in a string, that is
+    /// `<intraline whitespace>*<line ending><intraline whitespace>*`.
+    /// Returns false and consumes nothing if the chars don't form one.
+    fn line_continuation(&mut self) -> bool {
+        let mut lookahead = self.chars.clone();
+        let mut skipped = 0;
+        loop {
+            match lookahead.next() {
+                Some(c) if is_intraline_whitespace(c) => skipped += 1,
+                Some('\n') => break,
+                Some('\r') => {
+                    if lookahead.next() == Some('\n') {
+                        skipped += 1;
+                    }
+                    break;
+                }
+                _ => return false,
+            }
+        }
+        // the whitespace before the line ending and the line ending itself
+        for _ in 0..=skipped {
+            self.bump();
+        }
+        self.eat_while(is_intraline_whitespace);
+        true
     }
 
     fn line_comment(&mut self) -> Token {
-        let content = self.take_while(|c| c != '\n');
+        let content = self.take_while(|c| c != '\n' && c != '\r');
         Token::Comment(content.trim().into())
     }
 
@@ -207,28 +498,72 @@ impl<'a> Lexer<'a> {
         // throw away the '|'
         self.bump();
         let mut content = String::new();
-        while self.first() != Some('|') && self.second() != Some('#') {
-            content.push(self.bump().unwrap());
-        }
-        match (self.first(), self.second()) {
-            (Some('|'), Some('#')) => {
-                // throw away the '|' and '#'
-                self.bump();
-                self.bump();
-                Token::BlockComment(content.trim().into())
+        // block comments nest, only the '|#' pair closing the outermost '#|' ends the comment
+        let mut depth = 1;
+        loop {
+            match (self.first(), self.second()) {
+                (Some('|'), Some('#')) => {
+                    // throw away the '|' and '#'
+                    self.bump();
+                    self.bump();
+                    depth -= 1;
+                    if depth == 0 {
+                        return Token::BlockComment(content.trim().into());
+                    }
+                    content.push_str("|#");
+                }
+                (Some('#'), Some('|')) => {
+                    self.bump();
+                    self.bump();
+                    depth += 1;
+                    content.push_str("#|");
+                }
+                (Some(c), _) => {
+                    content.push(c);
+                    self.bump();
+                }
+                (None, _) => return Token::Error("unterminated block comment".into()),
             }
-            (_, _) => Token::Error,
         }
     }
 
+    fn comma_at(&mut self) -> Token {
+        self.bump(); // throw away the '@'
+        Token::CommaAt
+    }
+
+    fn datum_comment(&mut self) -> Token {
+        self.bump(); // throw away the ';'
+        Token::DatumComment
+    }
+
     fn directive(&mut self) -> Token {
         // throw away the '!'
         self.bump();
         let content = self.take_while(|c| !c.is_whitespace());
+        if content == DIRECTIVES[0] {
+            self.fold_case = true;
+        } else if content == DIRECTIVES[1] {
+            self.fold_case = false;
+        }
         Token::Directive(content)
     }
 }
 
+/// Yields every token up to, but not including, `Token::EOF`. After that it keeps returning `None`.
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        match self.next_token() {
+            Token::EOF => None,
+            token => Some(token),
+        }
+    }
+}
+
+impl<'a> FusedIterator for Lexer<'a> {}
+
 /// checks whether the letter i is a valid first letter of an identifier
 /// (can't be a number or invalid extended char)
 fn is_valid_first_letter_ident(c: char) -> bool {
@@ -240,23 +575,52 @@ fn is_identifier_char(c: char) -> bool {
     c.is_alphanumeric() || EXTENDED_IDENT_CHARS.contains(&c)
 }
 
+/// the char denoted by a character name, as in `#\newline`
+fn char_name(name: &str) -> Option<char> {
+    CHAR_NAMES
+        .iter()
+        .find(|&&(char_name, _)| char_name == name)
+        .map(|&(_, c)| c)
+}
+
+/// checks whether a char following a '#' starts a radix or exactness prefix
+fn is_number_prefix(c: char) -> bool {
+    matches!(c.to_ascii_lowercase(), 'b' | 'o' | 'd' | 'x' | 'e' | 'i')
+}
+
+/// checks whether a char is whitespace that doesn't end a line
+fn is_intraline_whitespace(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
     use Token::*;
 
+    fn error(message: &str) -> Token {
+        Error(message.into())
+    }
+
+    /// a decimal number literal without prefixes
+    fn number(text: &str) -> Token {
+        prefixed_number(text, 10, None)
+    }
+
+    fn prefixed_number(text: &str, radix: u32, exactness: Option<Exactness>) -> Token {
+        Literal(LiteralKind::Number(NumberLiteral {
+            text: text.into(),
+            radix,
+            exactness,
+        }))
+    }
+
     /// Tests that the sequences of tokens produced by the lexer matches the expected sequence.
     fn expected_sequnce(seq: &[Token], input: &str) {
         let mut lexer = Lexer::new(input);
-        let mut tokens = Vec::new();
-        loop {
-            let token = lexer.next_token();
-            if token == EOF {
-                break;
-            } else {
-                tokens.push(token);
-            }
-        }
+        let tokens: Vec<Token> = lexer.by_ref().collect();
         println!("expected: {:?}", seq);
         println!("actual:   {:?}", tokens);
         if tokens.len() != seq.len() {
@@ -274,6 +638,75 @@ mod test {
         expected_sequnce(&[], "");
     }
 
+    #[test]
+    fn empty_vectors() {
+        expected_sequnce(&[OpenVec, CloseParen], "#()");
+        expected_sequnce(&[OpenByteVec, CloseParen], "#u8()");
+        expected_sequnce(&[OpenVec, OpenByteVec, CloseParen, CloseParen], "#(#u8())");
+    }
+
+    #[test]
+    fn vector_spans() {
+        let mut lexer = Lexer::new("#u8(1)");
+        assert_eq!(OpenByteVec, lexer.next_token());
+        assert_eq!(span(0, 4, 1, 1), lexer.span());
+        assert_eq!(number("1"), lexer.next_token());
+        assert_eq!(span(4, 5, 1, 5), lexer.span());
+    }
+
+    #[test]
+    fn fold_case() {
+        expected_sequnce(
+            &[
+                Identifier("LamBDA".into()),
+                Directive("fold-case".into()),
+                Identifier("lambda".into()),
+                PipeIdentifier("LamBDA".into()),
+                Directive("no-fold-case".into()),
+                Identifier("LamBDA".into()),
+            ],
+            "LamBDA #!fold-case LamBDA |LamBDA| #!no-fold-case LamBDA",
+        );
+    }
+
+    #[test]
+    fn fold_case_skips_strings_and_chars() {
+        expected_sequnce(
+            &[
+                Directive("fold-case".into()),
+                Identifier("foo".into()),
+                Literal(LiteralKind::Str("BAR".into())),
+                Literal(LiteralKind::Char('C')),
+            ],
+            "#!fold-case FOO \"BAR\" #\\C",
+        );
+    }
+
+    #[test]
+    fn collect_empty() {
+        assert_eq!(Vec::<Token>::new(), Lexer::new("").collect::<Vec<_>>());
+        assert_eq!(Vec::<Token>::new(), Lexer::new("  \n ").collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn iterator_is_fused() {
+        let mut lexer = Lexer::new("a");
+        assert_eq!(Some(Identifier("a".into())), lexer.next());
+        for _ in 0..5 {
+            assert_eq!(None, lexer.next());
+        }
+    }
+
+    #[test]
+    fn eof_is_idempotent() {
+        let mut lexer = Lexer::new("()");
+        assert_eq!(OpenParen, lexer.next_token());
+        assert_eq!(CloseParen, lexer.next_token());
+        for _ in 0..5 {
+            assert_eq!(EOF, lexer.next_token());
+        }
+    }
+
     #[test]
     fn parens() {
         expected_sequnce(
@@ -291,6 +724,77 @@ mod test {
             "; this is a comment",
         );
     }
+    #[test]
+    fn comment_at_eof() {
+        // no trailing newline after the last comment
+        expected_sequnce(
+            &[
+                OpenParen,
+                Identifier("a".into()),
+                CloseParen,
+                Comment("final comment".into()),
+            ],
+            "(a)\n; final comment",
+        );
+    }
+
+    #[test]
+    fn block_comment() {
+        expected_sequnce(&[BlockComment("a | b # c".into())], "#| a | b # c |#");
+        expected_sequnce(
+            &[
+                BlockComment("comment".into()),
+                OpenParen,
+                Identifier("a".into()),
+                CloseParen,
+            ],
+            "#|comment|#(a)",
+        );
+    }
+
+    #[test]
+    fn nested_block_comment() {
+        expected_sequnce(
+            &[BlockComment("outer #| inner |# still outer".into())],
+            "#| outer #| inner |# still outer |#",
+        );
+        expected_sequnce(
+            &[
+                BlockComment("1 #| 2 #| 3 |# 2 |# 1".into()),
+                Identifier("a".into()),
+            ],
+            "#| 1 #| 2 #| 3 |# 2 |# 1 |# a",
+        );
+    }
+
+    #[test]
+    fn unterminated_block_comment() {
+        expected_sequnce(&[error("unterminated block comment")], "#| a | b # c");
+        expected_sequnce(
+            &[error("unterminated block comment")],
+            "#| outer #| inner |# still outer",
+        );
+    }
+
+    #[test]
+    fn datum_comment() {
+        expected_sequnce(
+            &[
+                OpenParen,
+                Identifier("a".into()),
+                DatumComment,
+                Identifier("b".into()),
+                Identifier("c".into()),
+                CloseParen,
+            ],
+            "(a #;b c)",
+        );
+        expected_sequnce(
+            &[DatumComment, OpenParen, Identifier("a".into()), CloseParen],
+            "#; (a)",
+        );
+    }
+
     #[test]
     fn single_char_ident() {
         expected_sequnce(&[Identifier(String::from("+"))], "+");
@@ -298,7 +802,8 @@ mod test {
 
     #[test]
     fn all_extended_char_idents() {
-        for ident in EXTENDED_IDENT_CHARS.iter() {
+        // a lone '.' is the dot of a dotted pair
+        for ident in EXTENDED_IDENT_CHARS.iter().filter(|c| **c != '.') {
             expected_sequnce(&[Identifier(String::from(*ident))], &ident.to_string());
         }
     }
@@ -316,28 +821,88 @@ mod test {
             "(+ var1 var2)",
         );
     }
+    #[test]
+    fn arrow_idents() {
+        expected_sequnce(&[Identifier(String::from("->"))], "->");
+        expected_sequnce(&[Identifier(String::from("a->b"))], "a->b");
+        expected_sequnce(
+            &[
+                OpenParen,
+                Identifier(String::from("->")),
+                Identifier(String::from("x")),
+                CloseParen,
+            ],
+            "(-> x)",
+        );
+    }
+
     #[test]
     fn multiple_idents() {
+        let ident = |name: &str| Identifier(String::from(name));
         let idents = [
-            "...",
-            "+",
-            "+soup+",
-            "<=?",
-            "->string",
-            "a34kTMNs",
-            "lambda",
-            "list->vector",
-            "q",
-            "V17a",
-            "|two words|",
-            "|two\x20;words|",
-            "the-word-recursion-has-many-meanings",
+            ("...", ident("...")),
+            ("+", ident("+")),
+            ("+soup+", ident("+soup+")),
+            ("<=?", ident("<=?")),
+            ("->string", ident("->string")),
+            ("a34kTMNs", ident("a34kTMNs")),
+            ("lambda", ident("lambda")),
+            ("list->vector", ident("list->vector")),
+            ("q", ident("q")),
+            ("V17a", ident("V17a")),
+            ("|two words|", PipeIdentifier(String::from("two words"))),
+            // `\x20` is a Rust escape here, so the lexer sees a plain space
+            (
+                "|two\x20;words|",
+                PipeIdentifier(String::from("two ;words")),
+            ),
+            (
+                "the-word-recursion-has-many-meanings",
+                ident("the-word-recursion-has-many-meanings"),
+            ),
         ];
-        for ident in idents {
-            expected_sequnce(&[Identifier(String::from(ident))], ident);
+        for (source, expected) in idents {
+            expected_sequnce(&[expected], source);
         }
     }
 
+    #[test]
+    fn pipe_idents() {
+        expected_sequnce(&[PipeIdentifier("two words".into())], "|two words|");
+        expected_sequnce(&[PipeIdentifier("".into())], "||");
+        // the closing bar is consumed exactly once
+        expected_sequnce(
+            &[
+                OpenParen,
+                PipeIdentifier("with".into()),
+                Identifier("x".into()),
+                PipeIdentifier("b".into()),
+                CloseParen,
+            ],
+            "(|with|x |b|)",
+        );
+        expected_sequnce(&[error("unterminated pipe identifier")], "|two words");
+    }
+
+    #[test]
+    fn pipe_ident_escapes() {
+        expected_sequnce(&[PipeIdentifier("foo|bar".into())], r"|foo\|bar|");
+        expected_sequnce(&[PipeIdentifier("two words".into())], r"|two\x20;words|");
+        expected_sequnce(&[PipeIdentifier("a\\b\nc\td".into())], r"|a\\b\nc\td|");
+        expected_sequnce(
+            &[
+                error("invalid pipe identifier escape at 1:3"),
+                Identifier("x".into()),
+            ],
+            r"|a\qb| x",
+        );
+        // no line continuations in identifiers
+        expected_sequnce(
+            &[error("invalid pipe identifier escape at 1:3")],
+            "|a\\\n b|",
+        );
+    }
+
     #[test]
     fn multiple_idents_one_str() {
         let idents = r#"...   +   +soup+   <=?   ->string   a34kTMNs   lambda   list->vector   q   V17a   |two words|   |two\x20;words|   the-word-recursion-has-many-meanings"#;
@@ -352,8 +917,8 @@ mod test {
             Identifier(String::from("list->vector")),
             Identifier(String::from("q")),
             Identifier(String::from("V17a")),
-            Identifier(String::from("|two words|")),
-            Identifier(String::from(r#"|two\x20;words|"#)),
+            PipeIdentifier(String::from("two words")),
+            PipeIdentifier(String::from("two words")),
             Identifier(String::from("the-word-recursion-has-many-meanings")),
         ];
         expected_sequnce(expected, idents);
@@ -387,4 +952,609 @@ mod test {
             r#""test"  "test"   "test"   "test""#,
         );
     }
+
+    #[test]
+    fn string_with_spaces() {
+        expected_sequnce(
+            &[
+                OpenParen,
+                Identifier("display".into()),
+                Literal(LiteralKind::Str("hello world".into())),
+                CloseParen,
+            ],
+            r#"(display "hello world")"#,
+        );
+        // an escaped quote doesn't end the string
+        expected_sequnce(
+            &[Literal(LiteralKind::Str(r#"say "hi" "#.into()))],
+            r#""say \"hi\" ""#,
+        );
+    }
+
+    #[test]
+    fn unterminated_string() {
+        expected_sequnce(&[error("unterminated string")], r#""hello world"#);
+        expected_sequnce(&[error("unterminated string")], r#""hello \""#);
+    }
+
+    #[test]
+    fn string_escapes() {
+        expected_sequnce(
+            &[Literal(LiteralKind::Str("a\nb\t\"c\"\\|".into()))],
+            r#""a\nb\t\"c\"\\\|""#,
+        );
+        expected_sequnce(
+            &[Literal(LiteralKind::Str("A B".into()))],
+            r#""\x41;\x20;B""#,
+        );
+    }
+
+    #[test]
+    fn invalid_string_escape() {
+        expected_sequnce(&[error("invalid string escape at 1:2")], r#""\q""#);
+        // the error points at the '\'
+        expected_sequnce(&[error("invalid string escape at 1:4")], r#""ab\q""#);
+        expected_sequnce(&[error("invalid string escape at 2:2")], "\"a\nb\\q\"");
+        // only the first invalid escape is reported
+        expected_sequnce(&[error("invalid string escape at 1:2")], r#""\q\q""#);
+        // the rest of the string is still consumed
+        expected_sequnce(
+            &[
+                error("invalid string escape at 1:3"),
+                Identifier("x".into()),
+            ],
+            r#""a\qb" x"#,
+        );
+        // a hex escape missing its ';' doesn't swallow the closing quote
+        expected_sequnce(
+            &[
+                error("invalid string escape at 1:2"),
+                Identifier("x".into()),
+            ],
+            r#""\x41" x"#,
+        );
+    }
+
+    #[test]
+    fn string_line_continuation() {
+        expected_sequnce(
+            &[Literal(LiteralKind::Str("foobar".into()))],
+            "\"foo\\\n   bar\"",
+        );
+        expected_sequnce(
+            &[Literal(LiteralKind::Str("foobar".into()))],
+            "\"foo\\  \t\r\n\tbar\"",
+        );
+        // newlines without a preceding '\' are kept as they are
+        expected_sequnce(
+            &[Literal(LiteralKind::Str("foo\n   bar".into()))],
+            "\"foo\n   bar\"",
+        );
+        // whitespace after a '\' must be followed by a line ending
+        expected_sequnce(&[error("invalid string escape at 1:5")], "\"foo\\  bar\"");
+    }
+
+    #[test]
+    fn string_hex_escapes() {
+        expected_sequnce(
+            &[Literal(LiteralKind::Str("\u{abcd}\u{ABCD}".into()))],
+            r#""\xaBcD;\xABCD;""#,
+        );
+        expected_sequnce(
+            &[Literal(LiteralKind::Str("\u{10FFFF}".into()))],
+            r#""\x10FFFF;""#,
+        );
+        // empty
+        expected_sequnce(&[error("invalid string escape at 1:2")], r#""\x;""#);
+        // above the unicode range
+        expected_sequnce(&[error("invalid string escape at 1:2")], r#""\x110000;""#);
+        expected_sequnce(
+            &[error("invalid string escape at 1:2")],
+            r#""\x1000000000;""#,
+        );
+        // surrogate
+        expected_sequnce(&[error("invalid string escape at 1:2")], r#""\xD800;""#);
+    }
+
+    #[test]
+    fn byte_pos_and_remaining() {
+        let mut lexer = Lexer::new("(a b)");
+        assert_eq!(0, lexer.byte_pos());
+        assert_eq!("(a b)", lexer.remaining());
+        assert_eq!(OpenParen, lexer.next_token());
+        assert_eq!(Identifier("a".into()), lexer.next_token());
+        assert_eq!(2, lexer.byte_pos());
+        assert_eq!(" b)", lexer.remaining());
+        // multi-byte chars advance the position by their encoded length
+        let mut lexer = Lexer::new("λ x");
+        assert_eq!(Identifier("λ".into()), lexer.next_token());
+        assert_eq!(2, lexer.byte_pos());
+        assert_eq!(" x", lexer.remaining());
+    }
+
+    #[test]
+    fn char_lit() {
+        expected_sequnce(
+            &[
+                Literal(LiteralKind::Char('a')),
+                Literal(LiteralKind::Char('Z')),
+                Literal(LiteralKind::Char('1')),
+                Literal(LiteralKind::Char('λ')),
+            ],
+            r"#\a #\Z #\1 #\λ",
+        );
+        expected_sequnce(
+            &[
+                OpenParen,
+                Identifier("list".into()),
+                Literal(LiteralKind::Char('x')),
+                CloseParen,
+            ],
+            r"(list #\x)",
+        );
+    }
+
+    #[test]
+    fn named_char_lit() {
+        let names = [
+            ("alarm", '\u{7}'),
+            ("backspace", '\u{8}'),
+            ("delete", '\u{7f}'),
+            ("escape", '\u{1b}'),
+            ("newline", '\n'),
+            ("null", '\0'),
+            ("return", '\r'),
+            ("space", ' '),
+            ("tab", '\t'),
+        ];
+        for (name, c) in names {
+            expected_sequnce(&[Literal(LiteralKind::Char(c))], &format!(r"#\{name}"));
+        }
+        // not `#\s` followed by `pace`
+        expected_sequnce(&[Literal(LiteralKind::Char(' ')), CloseParen], r"#\space)");
+        expected_sequnce(
+            &[error(r"unknown character name #\spaceship")],
+            r"#\spaceship",
+        );
+    }
+
+    #[test]
+    fn syntactic_char_lit() {
+        for c in ['\\', '|', '(', ')', '"', ';', '#', '\''] {
+            expected_sequnce(&[Literal(LiteralKind::Char(c))], &format!(r"#\{c}"));
+        }
+        expected_sequnce(
+            &[
+                OpenParen,
+                Literal(LiteralKind::Char('(')),
+                Literal(LiteralKind::Char(')')),
+                CloseParen,
+            ],
+            r"(#\( #\))",
+        );
+    }
+
+    #[test]
+    fn space_char_lit() {
+        // the char after `#\` is taken literally, even a space
+        expected_sequnce(&[Literal(LiteralKind::Char(' '))], r"#\ ");
+        expected_sequnce(
+            &[Literal(LiteralKind::Char(' ')), Identifier("a".into())],
+            r"#\  a",
+        );
+        expected_sequnce(&[error(r"expected a character after #\")], r"#\");
+    }
+
+    #[test]
+    fn glued_char_lit() {
+        expected_sequnce(&[error(r"malformed character #\12")], r"#\12");
+        expected_sequnce(&[error(r"malformed character #\1a")], r"#\1a");
+        expected_sequnce(&[error(r"malformed character #\(a"), CloseParen], r"#\(a)");
+        // delimiters may follow right away
+        expected_sequnce(
+            &[OpenParen, Literal(LiteralKind::Char('1')), CloseParen],
+            r"(#\1)",
+        );
+    }
+
+    #[test]
+    fn hex_char_lit() {
+        expected_sequnce(&[Literal(LiteralKind::Char('A'))], r"#\x41");
+        expected_sequnce(&[Literal(LiteralKind::Char('λ'))], r"#\x3bb");
+        expected_sequnce(&[Literal(LiteralKind::Char('\u{abcd}'))], r"#\xaBcD");
+        expected_sequnce(&[Literal(LiteralKind::Char('\u{10FFFF}'))], r"#\x10FFFF");
+        // without digits it's just the letter x
+        expected_sequnce(&[Literal(LiteralKind::Char('x'))], r"#\x");
+        // surrogate
+        expected_sequnce(
+            &[error(r"#\xD800 is not a valid unicode scalar value")],
+            r"#\xD800",
+        );
+        // above the unicode range
+        expected_sequnce(
+            &[error(r"#\x110000 is not a valid unicode scalar value")],
+            r"#\x110000",
+        );
+        expected_sequnce(
+            &[error(r"#\x1000000000 is not a valid unicode scalar value")],
+            r"#\x1000000000",
+        );
+        // not hex, and not a known name either
+        expected_sequnce(&[error(r"unknown character name #\xyz")], r"#\xyz");
+    }
+
+    #[test]
+    fn integers() {
+        expected_sequnce(
+            &[number("42"), number("-7"), number("+100"), number("0")],
+            "42 -7 +100 0",
+        );
+        expected_sequnce(
+            &[
+                OpenParen,
+                Identifier("+".into()),
+                number("1"),
+                number("-2"),
+                CloseParen,
+            ],
+            "(+ 1 -2)",
+        );
+    }
+
+    #[test]
+    fn signs_stay_identifiers() {
+        expected_sequnce(
+            &[
+                Identifier("+".into()),
+                Identifier("-".into()),
+                Identifier("-x".into()),
+                Identifier("+soup+".into()),
+            ],
+            "+ - -x +soup+",
+        );
+        // a sign and a dot need a digit after them to be a number
+        expected_sequnce(
+            &[Identifier("-.".into()), Identifier("+.x".into())],
+            "-. +.x",
+        );
+    }
+
+    #[test]
+    fn number_then_ident() {
+        // `1+` isn't an identifier, identifiers can't begin with a digit
+        expected_sequnce(&[number("1"), Identifier("+".into())], "1+");
+        expected_sequnce(&[number("1"), Identifier("-".into())], "1-");
+    }
+
+    #[test]
+    fn decimals() {
+        for text in [
+            "3.14", ".5", "2.", "5.", "-0.5", "+2.", "1.e5", "-.5", "+.25", "-.5e-2",
+        ] {
+            expected_sequnce(&[number(text)], text);
+        }
+        expected_sequnce(
+            &[OpenParen, number(".5"), number("2."), CloseParen],
+            "(.5 2.)",
+        );
+    }
+
+    #[test]
+    fn exponents() {
+        for text in ["1e10", "6.022e23", "1.5E-3", "2e+2", ".5e1"] {
+            expected_sequnce(&[number(text)], text);
+        }
+    }
+
+    #[test]
+    fn malformed_decimals() {
+        expected_sequnce(&[error("malformed number")], "1.2.3");
+        expected_sequnce(&[error("malformed number")], "1e2e3");
+        expected_sequnce(&[error("exponent has no digits")], "1e");
+        expected_sequnce(
+            &[error("exponent has no digits"), Identifier("x".into())],
+            "1e+ x",
+        );
+        expected_sequnce(&[error("malformed number")], ".5.");
+        expected_sequnce(&[error("malformed number")], "-.5.");
+    }
+
+    #[test]
+    fn dots_stay_identifiers() {
+        expected_sequnce(&[Identifier("...".into())], "...");
+        expected_sequnce(&[Identifier(".a".into())], ".a");
+        expected_sequnce(&[Identifier("->string".into())], "->string");
+    }
+
+    #[test]
+    fn dotted_pair() {
+        expected_sequnce(&[Dot], ".");
+        expected_sequnce(
+            &[
+                OpenParen,
+                Identifier("a".into()),
+                Dot,
+                Identifier("b".into()),
+                CloseParen,
+            ],
+            "(a . b)",
+        );
+        expected_sequnce(
+            &[
+                OpenParen,
+                Identifier("a".into()),
+                Dot,
+                OpenParen,
+                Identifier("b".into()),
+                CloseParen,
+                CloseParen,
+            ],
+            "(a .(b))",
+        );
+        expected_sequnce(
+            &[
+                OpenParen,
+                Identifier("a".into()),
+                Dot,
+                number(".5"),
+                CloseParen,
+            ],
+            "(a . .5)",
+        );
+    }
+
+    #[test]
+    fn radix_prefixes() {
+        for (text, radix, digits) in [
+            ("#xFF", 16, "FF"),
+            ("#xff", 16, "ff"),
+            ("#XfF", 16, "fF"),
+            ("#b1010", 2, "1010"),
+            ("#B-101", 2, "-101"),
+            ("#o17", 8, "17"),
+            ("#O+17", 8, "+17"),
+            ("#d42", 10, "42"),
+            ("#D-1.5e3", 10, "-1.5e3"),
+        ] {
+            expected_sequnce(&[prefixed_number(digits, radix, None)], text);
+        }
+        expected_sequnce(
+            &[
+                OpenParen,
+                prefixed_number("18", 16, None),
+                number("1.8"),
+                CloseParen,
+            ],
+            "(#x18 #d1.8)",
+        );
+    }
+
+    #[test]
+    fn exactness_prefixes() {
+        use Exactness::*;
+        for (text, radix, exactness, digits) in [
+            ("#e1", 10, Exact, "1"),
+            ("#i1.5", 10, Inexact, "1.5"),
+            ("#E#xFF", 16, Exact, "FF"),
+            ("#x#e10", 16, Exact, "10"),
+            ("#i#b-101", 2, Inexact, "-101"),
+            ("#B#I101", 2, Inexact, "101"),
+            ("#d#e.5", 10, Exact, ".5"),
+        ] {
+            expected_sequnce(&[prefixed_number(digits, radix, Some(exactness))], text);
+        }
+    }
+
+    #[test]
+    fn conflicting_prefixes() {
+        expected_sequnce(&[error("duplicate or conflicting number prefix")], "#e#i1");
+        expected_sequnce(&[error("duplicate or conflicting number prefix")], "#e#e1");
+        expected_sequnce(&[error("duplicate or conflicting number prefix")], "#x#b1");
+        expected_sequnce(
+            &[error("duplicate or conflicting number prefix")],
+            "#x#e#d1",
+        );
+        expected_sequnce(&[error("number has no digits"), CloseParen], "#e)");
+    }
+
+    #[test]
+    fn invalid_radix_digits() {
+        expected_sequnce(&[error("malformed number")], "#b102");
+        expected_sequnce(&[error("number has no digits")], "#o8");
+        expected_sequnce(&[error("malformed number")], "#xFG");
+        expected_sequnce(&[error("number has no digits")], "#x");
+        expected_sequnce(&[error("number has no digits"), CloseParen], "#x-)");
+    }
+
+    #[test]
+    fn fractions_require_decimal_radix() {
+        expected_sequnce(
+            &[error("fractional numbers require decimal radix")],
+            "#x1.8",
+        );
+        expected_sequnce(
+            &[error("fractional numbers require decimal radix")],
+            "#b1.1",
+        );
+        expected_sequnce(&[error("fractional numbers require decimal radix")], "#o1.");
+    }
+
+    #[test]
+    fn rationals() {
+        for text in ["1/3", "3/4", "-10/20", "+1/2", "1/0"] {
+            expected_sequnce(&[number(text)], text);
+        }
+        expected_sequnce(&[prefixed_number("-1/F", 16, None)], "#x-1/F");
+        expected_sequnce(
+            &[prefixed_number("1/3", 10, Some(Exactness::Inexact))],
+            "#i1/3",
+        );
+        expected_sequnce(
+            &[
+                OpenParen,
+                Identifier("/".into()),
+                number("1"),
+                number("2/3"),
+                CloseParen,
+            ],
+            "(/ 1 2/3)",
+        );
+    }
+
+    #[test]
+    fn malformed_rationals() {
+        expected_sequnce(&[error("rational number is missing its denominator")], "1/");
+        expected_sequnce(
+            &[
+                error("rational number is missing its denominator"),
+                CloseParen,
+            ],
+            "1/)",
+        );
+        expected_sequnce(&[error("malformed number")], "1/2/3");
+        expected_sequnce(&[error("malformed number")], "1/2.5");
+        expected_sequnce(&[error("malformed number")], "1.5/2");
+        expected_sequnce(
+            &[error("rational number is missing its denominator")],
+            "#b1/2",
+        );
+    }
+
+    #[test]
+    fn booleans() {
+        for text in ["t", "f", "true", "false"] {
+            expected_sequnce(
+                &[Literal(LiteralKind::Boolean(text.into()))],
+                &format!("#{text}"),
+            );
+        }
+        expected_sequnce(
+            &[
+                OpenParen,
+                Literal(LiteralKind::Boolean("t".into())),
+                Literal(LiteralKind::Boolean("false".into())),
+                CloseParen,
+            ],
+            "(#t #false)",
+        );
+    }
+
+    #[test]
+    fn invalid_booleans() {
+        expected_sequnce(&[error("invalid boolean #troo")], "#troo");
+        expected_sequnce(&[error("invalid boolean #tru")], "#tru");
+        expected_sequnce(&[error("invalid boolean #falsey"), CloseParen], "#falsey)");
+    }
+
+    #[test]
+    fn unknown_syntax() {
+        expected_sequnce(
+            &[error("unexpected character '#'"), Identifier("z".into())],
+            "#z",
+        );
+        expected_sequnce(
+            &[
+                error("datum label #123 must end with '=' or '#'"),
+                Identifier("x".into()),
+            ],
+            "#123x",
+        );
+    }
+
+    #[test]
+    fn quasiquote() {
+        expected_sequnce(
+            &[
+                Grave,
+                OpenParen,
+                Identifier("a".into()),
+                Comma,
+                Identifier("b".into()),
+                CommaAt,
+                Identifier("c".into()),
+                CloseParen,
+            ],
+            "`(a ,b ,@c)",
+        );
+        expected_sequnce(
+            &[
+                Comma,
+                CommaAt,
+                OpenParen,
+                CloseParen,
+                Comma,
+                Identifier("@".into()),
+            ],
+            ",,@() , @",
+        );
+    }
+
+    fn span(start: usize, end: usize, line: usize, col: usize) -> Span {
+        Span {
+            start,
+            end,
+            line,
+            col,
+        }
+    }
+
+    #[test]
+    fn spans() {
+        let mut lexer = Lexer::new("(foo \"λ\")\n  bar ; hi\n#t");
+        let expected = [
+            (OpenParen, span(0, 1, 1, 1)),
+            (Identifier("foo".into()), span(1, 4, 1, 2)),
+            // 'λ' is two bytes but one column
+            (Literal(LiteralKind::Str("λ".into())), span(5, 9, 1, 6)),
+            (CloseParen, span(9, 10, 1, 9)),
+            (Identifier("bar".into()), span(13, 16, 2, 3)),
+            (Comment("hi".into()), span(17, 21, 2, 7)),
+            (
+                Literal(LiteralKind::Boolean("t".into())),
+                span(22, 24, 3, 1),
+            ),
+            (EOF, span(24, 24, 3, 3)),
+        ];
+        for (token, span) in expected {
+            assert_eq!(Spanned { value: token, span }, lexer.next_spanned_token());
+        }
+    }
+
+    #[test]
+    fn spans_across_multiline_tokens() {
+        let mut lexer = Lexer::new("#| a\nb |# x \"1\n2\" y");
+        assert_eq!(BlockComment("a\nb".into()), lexer.next_token());
+        assert_eq!(span(0, 9, 1, 1), lexer.span());
+        assert_eq!(Identifier("x".into()), lexer.next_token());
+        assert_eq!(span(10, 11, 2, 6), lexer.span());
+        lexer.next_token();
+        assert_eq!(span(12, 17, 2, 8), lexer.span());
+        assert_eq!(Identifier("y".into()), lexer.next_token());
+        assert_eq!(span(18, 19, 3, 4), lexer.span());
+    }
+
+    #[test]
+    fn line_comment_line_endings() {
+        for input in ["; hi\r\n(foo)", "; hi\r(foo)", "; hi\n(foo)"] {
+            let mut lexer = Lexer::new(input);
+            assert_eq!(Comment("hi".into()), lexer.next_token());
+            assert_eq!(span(0, 4, 1, 1), lexer.span());
+            assert_eq!(OpenParen, lexer.next_token());
+            assert_eq!(2, lexer.span().line);
+            assert_eq!(1, lexer.span().col);
+        }
+    }
+
+    #[test]
+    fn nth_char_lookahead() {
+        let mut lexer = Lexer::new("aλ#u8(");
+        assert_eq!(Some('a'), lexer.nth_char(0));
+        assert_eq!(Some('λ'), lexer.nth_char(1));
+        assert_eq!(Some('('), lexer.nth_char(5));
+        assert_eq!(None, lexer.nth_char(6));
+        lexer.bump();
+        assert_eq!(Some('λ'), lexer.first());
+        assert_eq!(Some('#'), lexer.second());
+        assert_eq!(Some('u'), lexer.third());
+    }
 }
@@ -0,0 +1,16 @@
+//! A scheme implementation. So far only the lexer exists, the CLI in `main.rs` is built on top of it.
+//!
+//! Without the default `std` feature the crate is `no_std` and only needs `alloc`.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+// the test harness needs std even when the library doesn't
+#[cfg(all(test, not(feature = "std")))]
+#[macro_use]
+extern crate std;
+
+pub mod lexer;
+pub mod tokens;
+
+pub use lexer::Lexer;
+pub use tokens::{LiteralKind, Token};
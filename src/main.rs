@@ -1,30 +1,45 @@
-mod lexer;
-mod tokens;
+mod selftest;
 // stdlib imports
 use std::path::PathBuf;
 use std::{fs::read_to_string, path::Path};
 // external lib imports
-use anyhow::{anyhow, Context, Result}; // error handling
-use clap::Parser; // argument parsing
+use anyhow::{Context, Result}; // error handling
+use clap::{Parser, Subcommand}; // argument parsing
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
-// internal imports
-use lexer::Lexer;
 
 #[derive(Parser)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Cli {
-    script: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Files to load, in order, before running the scripts or starting the REPL
+    #[arg(long)]
+    load: Vec<PathBuf>,
+    /// Scripts to run in sequence. Starts the REPL if none are given.
+    /// A script named like a subcommand, e.g. `selftest`, has to be passed after `--`
+    scripts: Vec<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Runs a built-in suite of small programs and reports pass/fail
+    #[command(hide = true)]
+    Selftest,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    match cli.script {
-        Some(script_path) => run_interpreter(&script_path),
-        None => run_repl(),
-    }?;
-    Ok(())
+    if let Some(Command::Selftest) = cli.command {
+        return selftest::run();
+    }
+
+    run_scripts(&cli.load)?;
+    if cli.scripts.is_empty() {
+        run_repl()?;
+    }
+    run_scripts(&cli.scripts)
 }
 
 fn run_repl() -> Result<()> {
@@ -54,8 +69,50 @@ fn run_repl() -> Result<()> {
     Ok(())
 }
 
+/// Runs the scripts in order, stopping at the first one that fails
+fn run_scripts(paths: &[PathBuf]) -> Result<()> {
+    for path in paths {
+        run_interpreter(path)?;
+    }
+    Ok(())
+}
+
 fn run_interpreter(script_path: &Path) -> Result<()> {
-    let string: String = read_to_string(script_path).context("could not open script file")?;
+    let string: String = read_to_string(script_path)
+        .with_context(|| format!("could not open script file {}", script_path.display()))?;
     println!("{string}");
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn load_flags_keep_their_order() {
+        let cli = Cli::try_parse_from(["scheme-rs", "--load", "a.scm", "--load", "b.scm"]).unwrap();
+        assert_eq!(cli.load, [PathBuf::from("a.scm"), PathBuf::from("b.scm")]);
+        assert!(cli.scripts.is_empty());
+        assert!(cli.command.is_none());
+    }
+
+    #[test]
+    fn scripts_keep_their_order() {
+        let cli = Cli::try_parse_from(["scheme-rs", "first.scm", "second.scm"]).unwrap();
+        assert_eq!(
+            cli.scripts,
+            [PathBuf::from("first.scm"), PathBuf::from("second.scm")]
+        );
+        assert!(cli.load.is_empty());
+    }
+
+    #[test]
+    fn missing_script_is_named_in_the_error() {
+        // the first script exists in the repo, the second one nowhere
+        let present = Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/medium.scm");
+        let missing = std::env::temp_dir().join("scheme-rs-no-such-script.scm");
+
+        let error = run_scripts(&[present, missing.clone()]).unwrap_err();
+        assert!(error.to_string().contains(&missing.display().to_string()));
+    }
+}
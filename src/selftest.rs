@@ -0,0 +1,61 @@
+//! A built-in suite of small scheme programs, used to quickly check that a built binary works.
+// external lib imports
+use anyhow::{bail, Result};
+// internal imports
+use scheme_rs::{Lexer, Token};
+
+/// (name, source) pairs that should run through the pipeline without errors
+const PROGRAMS: [(&str, &str); 8] = [
+    ("parens", "(((())))"),
+    ("define", "(define (square x) (* x x))"),
+    ("arithmetic", "(+ 1 (* 2 -3))"),
+    ("booleans", "(if #t #false #f)"),
+    ("string", r#"(display "hello\tworld\n")"#),
+    ("comments", "; line comment\n#| block comment |# (a b)"),
+    ("quote", "'(a `(b ,c ,@d) e)"),
+    ("datum labels", "#0=(a b . #0#)"),
+];
+
+/// Runs every program of the suite and reports pass/fail for each of them.
+/// Returns an error if any of them failed.
+pub fn run() -> Result<()> {
+    let mut failed = 0;
+    for (name, source) in PROGRAMS {
+        match lex(source) {
+            Ok(()) => println!("selftest {name} ... ok"),
+            Err(message) => {
+                println!("selftest {name} ... FAILED: {message}");
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{failed} of {} selftests failed", PROGRAMS.len());
+    }
+    Ok(())
+}
+
+/// lexes the source to the end, returning the message of the first error token if there is one
+fn lex(source: &str) -> Result<(), String> {
+    let mut lexer = Lexer::new(source);
+    loop {
+        match lexer.next_token() {
+            Token::EOF => return Ok(()),
+            Token::Error(message) => {
+                let span = lexer.span();
+                return Err(format!("{message} (at {}:{})", span.line, span.col));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn selftest_passes() {
+        assert!(run().is_ok());
+    }
+}
@@ -1,15 +1,36 @@
+use alloc::string::String;
+use core::fmt::{self, Display, Formatter, Write};
+
+/// The named chars of `#\<name>` literals, e.g. `#\space`
+pub(crate) const CHAR_NAMES: [(&str, char); 9] = [
+    ("alarm", '\u{7}'),
+    ("backspace", '\u{8}'),
+    ("delete", '\u{7f}'),
+    ("escape", '\u{1b}'),
+    ("newline", '\n'),
+    ("null", '\0'),
+    ("return", '\r'),
+    ("space", ' '),
+    ("tab", '\t'),
+];
+
 #[derive(Debug, PartialEq, Eq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum Token {
-    /// a-z,A-Z,1-9,extended symbos "! $ % & * + - . / : < = > ? @ ^ _ ~" (a single "." is not a valid token though).
-    /// cannot also start with number
+    /// a-z,A-Z,1-9,extended symbos "! $ % & * + - . / : < = > ? @ ^ _ ~" (a single "." is a `Dot` though).
+    /// cannot also start with number. Identifiers enclosed with '|' are `PipeIdentifier`s instead
     Identifier(String),
-    /// Identifier enclosed with '|', has some special rules in it's contents
+    /// Identifier enclosed with '|', has some special rules in it's contents.
+    /// Holds the contents without the surrounding bars.
     PipeIdentifier(String),
     Comment(String),      // ;;comment to end of line
     BlockComment(String), // |# block comment #|
-    Directive(String),    // #!directive
-    DatumOpen(String),    // #number= - e.g #323=
-    DatumRef(String),     // #number#
+    /// #; - comments out the datum following it. The lexer only marks it,
+    /// it's up to the parser to drop the next datum.
+    DatumComment,
+    Directive(String), // #!directive
+    DatumOpen(String), // #number= - e.g #323=
+    DatumRef(String),  // #number#
     // parenthesis
     OpenParen,        // (
     CloseParen,       // )
@@ -19,13 +40,17 @@ pub enum Token {
     CloseCurlyParen,  // Reserved
     Apost,            // the ' char. Denotes literal data.
     Grave,            // the ` char. Denotes partially constant data.
+    Comma,            // the , char. Unquotes inside partially constant data.
+    CommaAt,          // ,@ - unquotes and splices inside partially constant data.
+    Dot,              // a lone '.', used in dotted pairs like (a . b)
     // Open paren for some list types
     OpenVec,     // #(
     OpenByteVec, // #u8(
     // Literals
     Literal(LiteralKind),
     /// Unknown token. Input contains non-defined syntax, or that couldn't be parsed!
-    Error,
+    /// Holds a message explaining what went wrong.
+    Error(String),
     // Last token generated. Every token stream should end with it.
     EOF, // end of file
 }
@@ -33,6 +58,252 @@ pub enum Token {
 #[derive(Debug, PartialEq, Eq)]
 pub enum LiteralKind {
     Str(String),
+    Char(char),
     Boolean(String),
-    Number(String),
+    Number(NumberLiteral),
+}
+
+/// Where a token is located in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// byte offset of the token's first char
+    pub start: usize,
+    /// byte offset just past the token's last char
+    pub end: usize,
+    /// line of the token's first char, starting at 1
+    pub line: usize,
+    /// column of the token's first char counted in chars, starting at 1
+    pub col: usize,
+}
+
+/// A value together with the span of source it was lexed from.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub span: Span,
+}
+
+/// A number literal as written in the source, not yet converted to a value.
+#[derive(Debug, PartialEq, Eq)]
+pub struct NumberLiteral {
+    /// The number's text without its prefixes, e.g. `-1.5e3` or `FF`
+    pub text: String,
+    /// 2, 8, 10 or 16, from the `#b`, `#o`, `#d` or `#x` prefix. Defaults to 10
+    pub radix: u32,
+    /// From the `#e` or `#i` prefix, if there was one
+    pub exactness: Option<Exactness>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exactness {
+    Exact,   // #e
+    Inexact, // #i
+}
+
+/// Payload free discriminant of a `Token`, for cheap classification and use as table keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum TokenKind {
+    Identifier,
+    PipeIdentifier,
+    Comment,
+    BlockComment,
+    DatumComment,
+    Directive,
+    DatumOpen,
+    DatumRef,
+    OpenParen,
+    CloseParen,
+    OpenSquareParen,
+    CloseSquareParen,
+    OpenCurlyParen,
+    CloseCurlyParen,
+    Apost,
+    Grave,
+    Comma,
+    CommaAt,
+    Dot,
+    OpenVec,
+    OpenByteVec,
+    Literal,
+    Error,
+    EOF,
+}
+
+impl Token {
+    pub fn kind(&self) -> TokenKind {
+        match self {
+            Token::Identifier(_) => TokenKind::Identifier,
+            Token::PipeIdentifier(_) => TokenKind::PipeIdentifier,
+            Token::Comment(_) => TokenKind::Comment,
+            Token::BlockComment(_) => TokenKind::BlockComment,
+            Token::DatumComment => TokenKind::DatumComment,
+            Token::Directive(_) => TokenKind::Directive,
+            Token::DatumOpen(_) => TokenKind::DatumOpen,
+            Token::DatumRef(_) => TokenKind::DatumRef,
+            Token::OpenParen => TokenKind::OpenParen,
+            Token::CloseParen => TokenKind::CloseParen,
+            Token::OpenSquareParen => TokenKind::OpenSquareParen,
+            Token::CloseSquareParen => TokenKind::CloseSquareParen,
+            Token::OpenCurlyParen => TokenKind::OpenCurlyParen,
+            Token::CloseCurlyParen => TokenKind::CloseCurlyParen,
+            Token::Apost => TokenKind::Apost,
+            Token::Grave => TokenKind::Grave,
+            Token::Comma => TokenKind::Comma,
+            Token::CommaAt => TokenKind::CommaAt,
+            Token::Dot => TokenKind::Dot,
+            Token::OpenVec => TokenKind::OpenVec,
+            Token::OpenByteVec => TokenKind::OpenByteVec,
+            Token::Literal(_) => TokenKind::Literal,
+            Token::Error(_) => TokenKind::Error,
+            Token::EOF => TokenKind::EOF,
+        }
+    }
+}
+
+/// Renders a token back to source text that lexes to the same token again.
+/// Comments run to the end of the line, so tokens following one have to be put on a new line.
+/// `Error` renders as its message and `EOF` as nothing, neither of which round-trips.
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Identifier(name) => f.write_str(name),
+            Token::PipeIdentifier(name) => write_escaped(f, name, '|'),
+            Token::Comment(text) => write!(f, "; {text}"),
+            Token::BlockComment(text) => write!(f, "#| {text} |#"),
+            Token::DatumComment => f.write_str("#;"),
+            Token::Directive(name) => write!(f, "#!{name}"),
+            Token::DatumOpen(label) => write!(f, "#{label}="),
+            Token::DatumRef(label) => write!(f, "#{label}#"),
+            Token::OpenParen => f.write_str("("),
+            Token::CloseParen => f.write_str(")"),
+            Token::OpenSquareParen => f.write_str("["),
+            Token::CloseSquareParen => f.write_str("]"),
+            Token::OpenCurlyParen => f.write_str("{"),
+            Token::CloseCurlyParen => f.write_str("}"),
+            Token::Apost => f.write_str("'"),
+            Token::Grave => f.write_str("`"),
+            Token::Comma => f.write_str(","),
+            Token::CommaAt => f.write_str(",@"),
+            Token::Dot => f.write_str("."),
+            Token::OpenVec => f.write_str("#("),
+            Token::OpenByteVec => f.write_str("#u8("),
+            Token::Literal(literal) => literal.fmt(f),
+            Token::Error(message) => f.write_str(message),
+            Token::EOF => Ok(()),
+        }
+    }
+}
+
+impl Display for LiteralKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LiteralKind::Str(s) => write_escaped(f, s, '"'),
+            LiteralKind::Char(c) => match CHAR_NAMES.iter().find(|&&(_, named)| named == *c) {
+                Some((name, _)) => write!(f, "#\\{name}"),
+                // unnamed whitespace and control chars would be lost or misread, so use their value
+                None if c.is_whitespace() || c.is_control() => write!(f, "#\\x{:x}", *c as u32),
+                None => write!(f, "#\\{c}"),
+            },
+            LiteralKind::Boolean(b) => write!(f, "#{b}"),
+            LiteralKind::Number(number) => number.fmt(f),
+        }
+    }
+}
+
+impl Display for NumberLiteral {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.exactness {
+            Some(Exactness::Exact) => f.write_str("#e")?,
+            Some(Exactness::Inexact) => f.write_str("#i")?,
+            None => {}
+        }
+        match self.radix {
+            2 => f.write_str("#b")?,
+            8 => f.write_str("#o")?,
+            16 => f.write_str("#x")?,
+            _ => {}
+        }
+        f.write_str(&self.text)
+    }
+}
+
+/// Writes `s` between two `delimiter`s, escaping what the lexer would otherwise read differently.
+fn write_escaped(f: &mut Formatter<'_>, s: &str, delimiter: char) -> fmt::Result {
+    f.write_char(delimiter)?;
+    for c in s.chars() {
+        match c {
+            '\u{7}' => f.write_str("\\a")?,
+            '\u{8}' => f.write_str("\\b")?,
+            '\t' => f.write_str("\\t")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\\' => f.write_str("\\\\")?,
+            c if c == delimiter => write!(f, "\\{c}")?,
+            c if c.is_control() => write!(f, "\\x{:x};", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char(delimiter)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lexer::Lexer;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn kind() {
+        assert_eq!(Token::Identifier("x".into()).kind(), TokenKind::Identifier);
+        assert_eq!(
+            Token::Literal(LiteralKind::Str("x".into())).kind(),
+            TokenKind::Literal
+        );
+        assert_eq!(Token::OpenParen.kind(), TokenKind::OpenParen);
+        assert_eq!(Token::EOF.kind(), TokenKind::EOF);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("(", Token::OpenParen.to_string());
+        assert_eq!(",@", Token::CommaAt.to_string());
+        assert_eq!("foo", Token::Identifier("foo".into()).to_string());
+        assert_eq!("|a\\|b|", Token::PipeIdentifier("a|b".into()).to_string());
+        assert_eq!(
+            "\"say \\\"hi\\\"\\n\"",
+            Token::Literal(LiteralKind::Str("say \"hi\"\n".into())).to_string()
+        );
+        assert_eq!(
+            "#\\space",
+            Token::Literal(LiteralKind::Char(' ')).to_string()
+        );
+        let number = NumberLiteral {
+            text: "FF".into(),
+            radix: 16,
+            exactness: Some(Exactness::Exact),
+        };
+        assert_eq!(
+            "#e#xFF",
+            Token::Literal(LiteralKind::Number(number)).to_string()
+        );
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let source = concat!(
+            "; a comment\n",
+            "#| block #| nested |# |# #!fold-case #;(x) #0=(a . #0#)\n",
+            "'(a `(b ,c ,@d)) #(1 2) #u8(3) [x] {y}\n",
+            "|pipe \\| ident\\x41;| \"str \\\\ \\\" \\t \\a \\x1;\" #true #f\n",
+            "#\\a #\\( #\\space #\\x3bb #\\x0 #\\xa0 #\\delete #\\x #\\\\\n",
+            "-.5 +12 1/2 6.022e23 #b101 #o17 #xff #e1.5 #i#d3 #x#i-F/2",
+        );
+        let tokens: Vec<Token> = Lexer::new(source).collect();
+        assert!(!tokens.iter().any(|t| t.kind() == TokenKind::Error));
+        let displayed: Vec<_> = tokens.iter().map(Token::to_string).collect();
+        let relexed: Vec<Token> = Lexer::new(&displayed.join("\n")).collect();
+        assert_eq!(tokens, relexed);
+    }
 }
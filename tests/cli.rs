@@ -0,0 +1,52 @@
+//! Runs the built binary the way a user would.
+#![cfg(feature = "std")]
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn scheme_rs() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_scheme-rs"))
+}
+
+/// A fresh directory for a test to put its files in, removed again when dropped,
+/// even if the test panics.
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("scheme-rs-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        ScratchDir(dir)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn selftest_exits_successfully() {
+    let output = scheme_rs().arg("selftest").output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("selftest parens ... ok"));
+    assert!(!stdout.contains("FAILED"));
+}
+
+#[test]
+fn script_named_selftest_runs_after_double_dash() {
+    let dir = ScratchDir::new("double-dash");
+    fs::write(dir.0.join("selftest"), "(display \"from script\")").unwrap();
+    let output = scheme_rs()
+        .args(["--", "selftest"])
+        .current_dir(&dir.0)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("from script"));
+    assert!(!stdout.contains("selftest parens"));
+}
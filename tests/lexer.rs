@@ -0,0 +1,51 @@
+//! Lexes programs through the crate's public API only.
+use scheme_rs::tokens::NumberLiteral;
+use scheme_rs::{Lexer, LiteralKind, Token};
+
+#[test]
+fn lex_small_program() {
+    let source = "; squares a number\n(define (square x) (* x x))\n(display \"hi\") #t 42";
+    let tokens: Vec<Token> = Lexer::new(source).collect();
+    let ident = |name: &str| Token::Identifier(name.into());
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Comment("squares a number".into()),
+            Token::OpenParen,
+            ident("define"),
+            Token::OpenParen,
+            ident("square"),
+            ident("x"),
+            Token::CloseParen,
+            Token::OpenParen,
+            ident("*"),
+            ident("x"),
+            ident("x"),
+            Token::CloseParen,
+            Token::CloseParen,
+            Token::OpenParen,
+            ident("display"),
+            Token::Literal(LiteralKind::Str("hi".into())),
+            Token::CloseParen,
+            Token::Literal(LiteralKind::Boolean("t".into())),
+            Token::Literal(LiteralKind::Number(NumberLiteral {
+                text: "42".into(),
+                radix: 10,
+                exactness: None,
+            })),
+        ]
+    );
+}
+
+#[test]
+fn spans_through_public_api() {
+    let mut lexer = Lexer::new("(a\n  b)");
+    let spanned: Vec<_> = core::iter::from_fn(|| {
+        let token = lexer.next_spanned_token();
+        (token.value != Token::EOF).then_some(token)
+    })
+    .collect();
+    let b = &spanned[2];
+    assert_eq!(Token::Identifier("b".into()), b.value);
+    assert_eq!((2, 3), (b.span.line, b.span.col));
+}